@@ -12,6 +12,63 @@ pub struct S3Event {
     pub records: Vec<S3Record>,
 }
 
+impl S3Event {
+    /// Parses an [`S3Event`] (or [`S3TestEvent`]) from the `body` of an SQS message, as
+    /// delivered when S3 bucket notifications are routed through an SQS queue.
+    pub fn from_sqs_body(body: &str) -> serde_json::Result<S3EventOrTest> {
+        serde_json::from_str(body)
+    }
+
+    /// Parses an [`S3Event`] (or [`S3TestEvent`]) from an SNS notification message, as
+    /// delivered when S3 bucket notifications are routed through SNS (optionally fanned out to
+    /// SQS from there). `msg` is the raw SNS notification JSON; its `Message` field holds the
+    /// stringified S3 event payload, which is parsed in turn.
+    pub fn from_sns_message(msg: &str) -> serde_json::Result<S3EventOrTest> {
+        #[derive(Deserialize)]
+        struct SnsNotification {
+            #[serde(rename = "Message")]
+            message: String,
+        }
+
+        let notification: SnsNotification = serde_json::from_str(msg)?;
+        serde_json::from_str(&notification.message)
+    }
+}
+
+/// Either an [`S3Event`] or the one-off [`S3TestEvent`] S3 sends when a notification
+/// configuration is first attached to a bucket.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum S3EventOrTest {
+    Event(S3Event),
+    TestEvent(S3TestEvent),
+}
+
+/// The test message S3 delivers when a notification configuration is first attached to a
+/// bucket, so consumers can distinguish it from a real [`S3Event`] instead of failing to parse.
+///
+/// [https://docs.aws.amazon.com/AmazonS3/latest/userguide/notification-content-structure.html](https://docs.aws.amazon.com/AmazonS3/latest/userguide/notification-content-structure.html)
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct S3TestEvent {
+    #[serde(rename = "Service")]
+    pub service: String,
+
+    #[serde(rename = "Event")]
+    pub event: String,
+
+    #[serde(rename = "Time")]
+    pub time: DateTime<Utc>,
+
+    #[serde(rename = "Bucket")]
+    pub bucket: String,
+
+    #[serde(rename = "RequestId")]
+    pub request_id: String,
+
+    #[serde(rename = "HostId")]
+    pub host_id: String,
+}
+
 /// `S3EventRecord` which wrap record data
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -53,13 +110,82 @@ pub struct S3Record {
 
     ///
     pub s3: S3Entity,
-    /* the following is from https://docs.aws.amazon.com/AmazonS3/latest/userguide/notification-content-structure.html, add?
-     * The glacierEventData key is only visible for s3:ObjectRestore:Completed events.
-     * The restoreEventData key contains attributes that are related to your restore request.
-     * The replicationEventData key is only visible for replication events.
-     * The intelligentTieringEventData key is only visible for S3 Intelligent-Tiering events.
-     * The lifecycleEventData key is only visible for S3 Lifecycle transition events.
-     */
+
+    /// Only present for `s3:ObjectRestore:Completed` events.
+    #[serde(default)]
+    pub glacier_event_data: Option<GlacierEventData>,
+
+    /// Only present for cross-Region replication events (schema version 2.2).
+    #[serde(default)]
+    pub replication_event_data: Option<ReplicationEventData>,
+
+    /// Only present for S3 Intelligent-Tiering events (schema version 2.3).
+    #[serde(default)]
+    pub intelligent_tiering_event_data: Option<IntelligentTieringEventData>,
+
+    /// Only present for S3 Lifecycle transition events (schema version 2.3).
+    #[serde(default)]
+    pub lifecycle_event_data: Option<LifecycleEventData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlacierEventData {
+    /// The attributes related to your restore request.
+    pub restore_event_data: RestoreEventData,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreEventData {
+    /// The time when Amazon S3 is scheduled to delete the restored copy of the object. Left as
+    /// a raw string, as its exact wire format isn't documented by AWS.
+    pub lifecycle_restoration_expiry_time: String,
+
+    /// The source storage class for the restored object.
+    pub lifecycle_restore_storage_class: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicationEventData {
+    /// The ID of the replication rule that triggered the event.
+    pub replication_rule_id: String,
+
+    /// The bucket the object was replicated to.
+    pub destination_bucket: String,
+
+    /// The S3 operation that the event relates to.
+    pub s3_operation: String,
+
+    /// The time the replica was last updated. Left as a raw string, as its exact wire format
+    /// isn't documented by AWS.
+    pub request_time: String,
+
+    /// The reason the replication failed, if applicable.
+    #[serde(default)]
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntelligentTieringEventData {
+    /// The S3 Intelligent-Tiering access tier the object was moved to.
+    pub destination_access_tier: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LifecycleEventData {
+    /// The attributes related to the lifecycle transition.
+    pub transition_event_data: TransitionEventData,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransitionEventData {
+    /// The storage class the object transitioned to.
+    pub destination_storage_class: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -119,11 +245,10 @@ pub struct S3Bucket {
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct S3Object {
-    /// The object key
+    /// The object key, percent-/plus-encoded as delivered by S3. Use [`S3Object::url_decoded_key`]
+    /// to get the key in a form that can be fed directly into a `GetObject` call.
     pub key: String,
 
-    /* This is not actually part of the message. Java calculates this: https://github.com/aws/aws-sdk-java/blob/6a4c873c71320ef0175ca1c13188e9c850a85e51/aws-java-sdk-s3/src/main/java/com/amazonaws/services/s3/event/S3EventNotification.java#L176-L183
-    pub url_decoded_key: Option<String>, */
     /// The object size in bytes
     #[serde(default)]
     pub size: Option<i64>,
@@ -156,6 +281,59 @@ pub struct S3Object {
     pub sequencer: Option<String>,
 }
 
+impl S3Object {
+    /// Compares the `sequencer` of this object with another to determine which event occurred
+    /// later, as described on [`S3Object::sequencer`].
+    ///
+    /// Returns `None` if either object's `sequencer` is `None`. The result is only meaningful
+    /// for events on the same object key; the comparison is undefined across different keys.
+    pub fn sequencer_cmp(&self, other: &S3Object) -> Option<std::cmp::Ordering> {
+        let a = self.sequencer.as_ref()?;
+        let b = other.sequencer.as_ref()?;
+        let len = a.len().max(b.len());
+        let pad = |s: &str| format!("{:0<width$}", s, width = len);
+        Some(pad(a).cmp(&pad(b)))
+    }
+
+    /// Decodes [`S3Object::key`] the same way the AWS SDKs do before handing it to S3 APIs: `+`
+    /// is treated as a space, and `%XX` percent-escapes are decoded as UTF-8 byte sequences.
+    ///
+    /// See [the Java SDK's equivalent](https://github.com/aws/aws-sdk-java/blob/6a4c873c71320ef0175ca1c13188e9c850a85e51/aws-java-sdk-s3/src/main/java/com/amazonaws/services/s3/event/S3EventNotification.java#L176-L183).
+    pub fn url_decoded_key(&self) -> Result<String, std::string::FromUtf8Error> {
+        let bytes = self.key.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'+' => {
+                    decoded.push(b' ');
+                    i += 1;
+                }
+                b'%' if i + 2 < bytes.len() => {
+                    let hex_byte = std::str::from_utf8(&bytes[i + 1..i + 3])
+                        .ok()
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                    match hex_byte {
+                        Some(byte) => {
+                            decoded.push(byte);
+                            i += 3;
+                        }
+                        None => {
+                            decoded.push(bytes[i]);
+                            i += 1;
+                        }
+                    }
+                }
+                byte => {
+                    decoded.push(byte);
+                    i += 1;
+                }
+            }
+        }
+        String::from_utf8(decoded)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -194,4 +372,214 @@ mod test {
         let reparsed: S3Event = serde_json::from_slice(output.as_bytes()).unwrap();
         assert_eq!(parsed, reparsed);
     }
+
+    #[test]
+    #[cfg(feature = "s3")]
+    fn example_s3_event_objectrestore_completed() {
+        let data = include_bytes!("test_data/s3-event-objectrestore-completed.json");
+        let parsed: S3Event = serde_json::from_slice(data).unwrap();
+        println!("--> {:?} <--", parsed);
+        let output: String = serde_json::to_string(&parsed).unwrap();
+        let reparsed: S3Event = serde_json::from_slice(output.as_bytes()).unwrap();
+        assert_eq!(parsed, reparsed);
+
+        let glacier_event_data = parsed.records[0]
+            .glacier_event_data
+            .as_ref()
+            .expect("glacierEventData should be present");
+        assert_eq!(
+            glacier_event_data.restore_event_data.lifecycle_restore_storage_class,
+            "GLACIER"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "s3")]
+    fn example_s3_event_replication() {
+        let data = include_bytes!("test_data/s3-event-replication.json");
+        let parsed: S3Event = serde_json::from_slice(data).unwrap();
+        println!("--> {:?} <--", parsed);
+        let output: String = serde_json::to_string(&parsed).unwrap();
+        let reparsed: S3Event = serde_json::from_slice(output.as_bytes()).unwrap();
+        assert_eq!(parsed, reparsed);
+
+        let replication_event_data = parsed.records[0]
+            .replication_event_data
+            .as_ref()
+            .expect("replicationEventData should be present");
+        assert_eq!(replication_event_data.replication_rule_id, "rule-1");
+        assert_eq!(replication_event_data.failure_reason, None);
+    }
+
+    #[test]
+    #[cfg(feature = "s3")]
+    fn example_s3_event_intelligent_tiering() {
+        let data = include_bytes!("test_data/s3-event-intelligent-tiering.json");
+        let parsed: S3Event = serde_json::from_slice(data).unwrap();
+        println!("--> {:?} <--", parsed);
+        let output: String = serde_json::to_string(&parsed).unwrap();
+        let reparsed: S3Event = serde_json::from_slice(output.as_bytes()).unwrap();
+        assert_eq!(parsed, reparsed);
+
+        let intelligent_tiering_event_data = parsed.records[0]
+            .intelligent_tiering_event_data
+            .as_ref()
+            .expect("intelligentTieringEventData should be present");
+        assert_eq!(
+            intelligent_tiering_event_data.destination_access_tier,
+            "ARCHIVE_ACCESS"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "s3")]
+    fn example_s3_event_lifecycle_transition() {
+        let data = include_bytes!("test_data/s3-event-lifecycle-transition.json");
+        let parsed: S3Event = serde_json::from_slice(data).unwrap();
+        println!("--> {:?} <--", parsed);
+        let output: String = serde_json::to_string(&parsed).unwrap();
+        let reparsed: S3Event = serde_json::from_slice(output.as_bytes()).unwrap();
+        assert_eq!(parsed, reparsed);
+
+        let lifecycle_event_data = parsed.records[0]
+            .lifecycle_event_data
+            .as_ref()
+            .expect("lifecycleEventData should be present");
+        assert_eq!(
+            lifecycle_event_data.transition_event_data.destination_storage_class,
+            "GLACIER"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "s3")]
+    fn example_s3_test_event() {
+        let data = include_bytes!("test_data/s3-event-test-event.json");
+        let parsed: S3TestEvent = serde_json::from_slice(data).unwrap();
+        println!("--> {:?} <--", parsed);
+        let output: String = serde_json::to_string(&parsed).unwrap();
+        let reparsed: S3TestEvent = serde_json::from_slice(output.as_bytes()).unwrap();
+        assert_eq!(parsed, reparsed);
+    }
+
+    #[test]
+    #[cfg(feature = "s3")]
+    fn example_s3_event_or_test_event() {
+        let data = include_bytes!("test_data/s3-event-test-event.json");
+        let parsed: S3EventOrTest = serde_json::from_slice(data).unwrap();
+        assert!(matches!(parsed, S3EventOrTest::TestEvent(_)));
+    }
+
+    fn s3_object_with_sequencer(sequencer: Option<&str>) -> S3Object {
+        S3Object {
+            key: "test-key".to_string(),
+            size: None,
+            version_id: None,
+            e_tag: None,
+            sequencer: sequencer.map(|s| s.to_string()),
+        }
+    }
+
+    #[test]
+    fn sequencer_cmp_orders_same_length_sequencers() {
+        let earlier = s3_object_with_sequencer(Some("0055AED6DCD90281E5"));
+        let later = s3_object_with_sequencer(Some("0055AED6DCD90281E6"));
+        assert_eq!(earlier.sequencer_cmp(&later), Some(std::cmp::Ordering::Less));
+        assert_eq!(later.sequencer_cmp(&earlier), Some(std::cmp::Ordering::Greater));
+    }
+
+    #[test]
+    fn sequencer_cmp_pads_shorter_sequencer() {
+        let shorter = s3_object_with_sequencer(Some("1"));
+        let longer = s3_object_with_sequencer(Some("10"));
+        assert_eq!(shorter.sequencer_cmp(&longer), Some(std::cmp::Ordering::Equal));
+    }
+
+    #[test]
+    fn sequencer_cmp_is_none_without_both_sequencers() {
+        let with_sequencer = s3_object_with_sequencer(Some("0055AED6DCD90281E5"));
+        let without_sequencer = s3_object_with_sequencer(None);
+        assert_eq!(with_sequencer.sequencer_cmp(&without_sequencer), None);
+        assert_eq!(without_sequencer.sequencer_cmp(&with_sequencer), None);
+    }
+
+    #[test]
+    fn url_decoded_key_decodes_plus_and_percent_escapes() {
+        let object = S3Object {
+            key: "a+b%2Fc/%E2%9C%93.txt".to_string(),
+            size: None,
+            version_id: None,
+            e_tag: None,
+            sequencer: None,
+        };
+        assert_eq!(object.url_decoded_key().unwrap(), "a b/c/✓.txt");
+    }
+
+    #[test]
+    fn url_decoded_key_passes_through_unescaped_key() {
+        let object = s3_object_with_sequencer(None);
+        assert_eq!(object.url_decoded_key().unwrap(), "test-key");
+    }
+
+    #[test]
+    #[cfg(feature = "s3")]
+    fn example_s3_event_objectremoved_delete_from_sqs_body() {
+        let data = include_bytes!("test_data/s3-event-objectremoved-delete-sqs-body.json");
+        let body = std::str::from_utf8(data).unwrap();
+        let parsed = S3Event::from_sqs_body(body).unwrap();
+        let event = match parsed {
+            S3EventOrTest::Event(event) => event,
+            S3EventOrTest::TestEvent(_) => panic!("expected an S3Event"),
+        };
+
+        let record = &event.records[0];
+        assert_eq!(record.event_name, "ObjectRemoved:Delete");
+        assert_eq!(record.s3.object.size, None);
+        assert_eq!(record.s3.object.e_tag, None);
+        assert_eq!(record.s3.object.version_id, None);
+
+        let output = serde_json::to_string(&event).unwrap();
+        let reparsed: S3Event = serde_json::from_str(&output).unwrap();
+        assert_eq!(event, reparsed);
+    }
+
+    #[test]
+    #[cfg(feature = "s3")]
+    fn example_s3_event_objectremoved_delete_from_sns_message() {
+        let data = include_bytes!("test_data/s3-event-objectremoved-delete-sns-message.json");
+        let msg = std::str::from_utf8(data).unwrap();
+        let parsed = S3Event::from_sns_message(msg).unwrap();
+        let event = match parsed {
+            S3EventOrTest::Event(event) => event,
+            S3EventOrTest::TestEvent(_) => panic!("expected an S3Event"),
+        };
+
+        let record = &event.records[0];
+        assert_eq!(record.event_name, "ObjectRemoved:Delete");
+        assert_eq!(record.s3.object.size, None);
+        assert_eq!(record.s3.object.e_tag, None);
+        assert_eq!(record.s3.object.version_id, None);
+
+        let output = serde_json::to_string(&event).unwrap();
+        let reparsed: S3Event = serde_json::from_str(&output).unwrap();
+        assert_eq!(event, reparsed);
+    }
+
+    #[test]
+    #[cfg(feature = "s3")]
+    fn example_s3_test_event_from_sqs_body() {
+        let data = include_bytes!("test_data/s3-event-test-event-sqs-body.json");
+        let body = std::str::from_utf8(data).unwrap();
+        let parsed = S3Event::from_sqs_body(body).unwrap();
+        assert!(matches!(parsed, S3EventOrTest::TestEvent(_)));
+    }
+
+    #[test]
+    #[cfg(feature = "s3")]
+    fn example_s3_test_event_from_sns_message() {
+        let data = include_bytes!("test_data/s3-event-test-event-sns-message.json");
+        let msg = std::str::from_utf8(data).unwrap();
+        let parsed = S3Event::from_sns_message(msg).unwrap();
+        assert!(matches!(parsed, S3EventOrTest::TestEvent(_)));
+    }
 }